@@ -0,0 +1,252 @@
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+
+use pathdiff::diff_paths;
+use serde_json::Value;
+
+use crate::configuration_file::{ConfigurationFile, WriteError};
+use crate::io::FromFileError;
+use crate::monorepo_manifest::{EnumeratePackageManifestsError, MonorepoManifest};
+use crate::opts::Action;
+use crate::package_manifest::PackageManifest;
+
+const IMPORT_MAP_FILENAME: &str = "import_map.json";
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct ImportMapError {
+    pub kind: ImportMapErrorKind,
+}
+
+impl Display for ImportMapError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.kind {
+            ImportMapErrorKind::ImportMapOutOfDate => {
+                write!(f, "import map is not up-to-date")
+            }
+            _ => write!(f, "error emitting import map"),
+        }
+    }
+}
+
+impl std::error::Error for ImportMapError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.kind {
+            ImportMapErrorKind::EnumeratePackageManifests(err) => Some(err),
+            ImportMapErrorKind::FromFile(err) => Some(err),
+            ImportMapErrorKind::Write(err) => Some(err),
+            ImportMapErrorKind::ImportMapOutOfDate => None,
+        }
+    }
+}
+
+impl From<EnumeratePackageManifestsError> for ImportMapError {
+    fn from(err: EnumeratePackageManifestsError) -> Self {
+        Self {
+            kind: ImportMapErrorKind::EnumeratePackageManifests(err),
+        }
+    }
+}
+
+impl From<FromFileError> for ImportMapError {
+    fn from(err: FromFileError) -> Self {
+        Self {
+            kind: ImportMapErrorKind::FromFile(err),
+        }
+    }
+}
+
+impl From<WriteError> for ImportMapError {
+    fn from(err: WriteError) -> Self {
+        Self {
+            kind: ImportMapErrorKind::Write(err),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ImportMapErrorKind {
+    #[non_exhaustive]
+    EnumeratePackageManifests(EnumeratePackageManifestsError),
+    #[non_exhaustive]
+    FromFile(FromFileError),
+    #[non_exhaustive]
+    Write(WriteError),
+    ImportMapOutOfDate,
+}
+
+/// The root `import_map.json`, a Deno/browser-style import map mapping each internal package's
+/// name to the relative path of its entrypoint.
+struct ImportMapConfig {
+    contents: Value,
+}
+
+impl ConfigurationFile for ImportMapConfig {
+    fn path(&self) -> PathBuf {
+        PathBuf::from(IMPORT_MAP_FILENAME)
+    }
+
+    fn from_directory(root: &Path, directory: &Path) -> Result<Self, FromFileError> {
+        let path = root.join(directory).join(IMPORT_MAP_FILENAME);
+        let contents = if path.exists() {
+            crate::io::from_json_file(&path)?
+        } else {
+            Value::Object(serde_json::Map::new())
+        };
+        Ok(Self { contents })
+    }
+
+    fn write(root: &Path, config: Self) -> Result<(), WriteError> {
+        crate::configuration_file::write_json(root, &config.path(), &config.contents)
+    }
+}
+
+/// Pick the entrypoint-relative-path field to use, preferring (in order) the `"."` entry of
+/// `exports`, then `main`, then `types`. `main`/`exports` are checked before `types` because the
+/// import map is consumed by runtimes resolving executable modules, not type declarations.
+fn select_entrypoint_relative_path<'a>(
+    from_exports: Option<&'a str>,
+    main: Option<&'a str>,
+    types: Option<&'a str>,
+) -> Option<&'a str> {
+    from_exports.or(main).or(types)
+}
+
+/// Resolve a package's entrypoint from its manifest to an absolute path, falling back to the
+/// package directory itself when none of `exports`, `main`, or `types` are present.
+fn resolve_entrypoint(root: &Path, package_manifest: &PackageManifest) -> PathBuf {
+    let from_exports = package_manifest
+        .exports()
+        .and_then(|exports| exports.get("."))
+        .and_then(Value::as_str);
+
+    let relative =
+        select_entrypoint_relative_path(from_exports, package_manifest.main(), package_manifest.types());
+
+    let directory = root.join(package_manifest.directory());
+    match relative {
+        Some(relative) => directory.join(relative),
+        None => directory,
+    }
+}
+
+/// Format a path, relative to the monorepo root, as a bare-specifier-safe import map value (e.g.
+/// `packages/foo/src/index.ts` -> `./packages/foo/src/index.ts`); paths that already start with
+/// `.` are left untouched.
+fn specifier_from_relative_path(relative: &str) -> String {
+    if relative.starts_with('.') {
+        relative.to_owned()
+    } else {
+        format!("./{relative}")
+    }
+}
+
+fn desired_import_map(
+    root: &Path,
+    package_manifest_by_package_name: &std::collections::HashMap<String, PackageManifest>,
+) -> Value {
+    let imports: BTreeMap<String, String> = package_manifest_by_package_name
+        .values()
+        .map(|package_manifest| {
+            let entrypoint = resolve_entrypoint(root, package_manifest);
+            let relative = diff_paths(&entrypoint, root).expect(
+                "Unable to calculate a relative path to package entrypoint from monorepo root",
+            );
+            let relative = relative.to_str().expect("Path not valid UTF-8 encoded");
+            let specifier = specifier_from_relative_path(relative);
+            (package_manifest.name().to_owned(), specifier)
+        })
+        .collect();
+
+    let mut contents = serde_json::Map::new();
+    contents.insert(
+        String::from("imports"),
+        serde_json::to_value(imports).expect("Should be able to serialize import map imports"),
+    );
+    Value::Object(contents)
+}
+
+/// Emit (or, in `Action::Lint` mode, check) the root `import_map.json` derived from the internal
+/// package topology, so consumers can run the monorepo's internal packages in import-map-aware
+/// runtimes and bundlers without publishing them.
+pub fn emit_import_map<P>(root: P, action: Action) -> Result<(), ImportMapError>
+where
+    P: AsRef<Path>,
+{
+    let root = root.as_ref();
+    let lerna_manifest = MonorepoManifest::from_directory(root)?;
+    let package_manifest_by_package_name = lerna_manifest.package_manifests_by_package_name()?;
+
+    let desired_contents = desired_import_map(root, &package_manifest_by_package_name);
+    let current = ImportMapConfig::from_directory(root, Path::new(""))?;
+
+    if current.contents == desired_contents {
+        return Ok(());
+    }
+
+    if action == Action::Lint {
+        println!(
+            "File has out-of-date import map: {:?}, expecting:",
+            current.path()
+        );
+        let serialized = serde_json::to_string_pretty(&desired_contents)
+            .expect("Should be able to serialize import map");
+        println!("{}", serialized);
+        return Err(ImportMapError {
+            kind: ImportMapErrorKind::ImportMapOutOfDate,
+        });
+    }
+
+    ImportMapConfig::write(
+        root,
+        ImportMapConfig {
+            contents: desired_contents,
+        },
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn select_entrypoint_relative_path_prefers_exports_over_main_and_types() {
+        let selected = select_entrypoint_relative_path(Some("exports.js"), Some("main.js"), Some("types.d.ts"));
+        assert_eq!(selected, Some("exports.js"));
+    }
+
+    #[test]
+    fn select_entrypoint_relative_path_prefers_main_over_types() {
+        let selected = select_entrypoint_relative_path(None, Some("main.js"), Some("types.d.ts"));
+        assert_eq!(selected, Some("main.js"));
+    }
+
+    #[test]
+    fn select_entrypoint_relative_path_falls_back_to_types() {
+        let selected = select_entrypoint_relative_path(None, None, Some("types.d.ts"));
+        assert_eq!(selected, Some("types.d.ts"));
+    }
+
+    #[test]
+    fn select_entrypoint_relative_path_is_none_when_nothing_is_present() {
+        assert_eq!(select_entrypoint_relative_path(None, None, None), None);
+    }
+
+    #[test]
+    fn specifier_from_relative_path_adds_a_dot_slash_prefix() {
+        assert_eq!(
+            specifier_from_relative_path("packages/foo/src/index.ts"),
+            "./packages/foo/src/index.ts"
+        );
+    }
+
+    #[test]
+    fn specifier_from_relative_path_leaves_a_dot_prefixed_path_alone() {
+        assert_eq!(
+            specifier_from_relative_path("../shared/index.ts"),
+            "../shared/index.ts"
+        );
+    }
+}
@@ -3,14 +3,17 @@ use std::fmt::Display;
 use std::path::{Path, PathBuf};
 
 use pathdiff::diff_paths;
+use serde_json::{Map, Value};
 
 use crate::configuration_file::{ConfigurationFile, WriteError};
 use crate::io::FromFileError;
 use crate::monorepo_manifest::{EnumeratePackageManifestsError, MonorepoManifest};
 use crate::opts::Action;
 use crate::package_manifest::PackageManifest;
+use crate::patch_config::PatchConfig;
 use crate::typescript_config::{
     TypescriptConfig, TypescriptParentProjectReference, TypescriptProjectReference,
+    TypescriptSettingsConfig,
 };
 
 #[derive(Debug)]
@@ -25,6 +28,13 @@ impl Display for LinkError {
             LinkErrorKind::ProjectReferencesOutOfDate => {
                 write!(f, "TypeScript project references are not up-to-date")
             }
+            LinkErrorKind::DependencyCycle(cycle) => {
+                write!(
+                    f,
+                    "internal packages form a dependency cycle, involving: {}",
+                    cycle.join(", ")
+                )
+            }
             _ => write!(f, "error linking TypeScript project references"),
         }
     }
@@ -37,6 +47,7 @@ impl std::error::Error for LinkError {
             LinkErrorKind::FromFile(err) => Some(err),
             LinkErrorKind::Write(err) => Some(err),
             LinkErrorKind::ProjectReferencesOutOfDate => None,
+            LinkErrorKind::DependencyCycle(_) => None,
         }
     }
 }
@@ -76,6 +87,9 @@ pub enum LinkErrorKind {
     // FIXME: this isn't an error
     #[non_exhaustive]
     ProjectReferencesOutOfDate,
+    /// The internal package graph is not a DAG. Carries the names of every package in the
+    /// offending strongly-connected component (sorted, not in edge order).
+    DependencyCycle(Vec<String>),
 }
 
 fn key_children_by_parent(
@@ -115,13 +129,12 @@ fn create_project_references(mut children: Vec<String>) -> Vec<TypescriptProject
 fn link_children_packages(
     root: &Path,
     action: Action,
-    lerna_manifest: &MonorepoManifest,
+    package_manifest_by_package_name: &HashMap<String, PackageManifest>,
 ) -> Result<bool, LinkError> {
     let mut is_exit_success = true;
 
-    lerna_manifest
-        .internal_package_manifests()?
-        .iter()
+    package_manifest_by_package_name
+        .values()
         .fold(HashMap::new(), key_children_by_parent)
         .into_iter()
         .try_for_each(|(directory, children)| -> Result<(), LinkError> {
@@ -151,27 +164,150 @@ fn link_children_packages(
     Ok(is_exit_success)
 }
 
+// Per-node state for Tarjan's strongly-connected-components algorithm.
+struct TarjanState {
+    index: usize,
+    lowlink: usize,
+    on_stack: bool,
+}
+
+/// Detect cycles in the internal package dependency graph using Tarjan's SCC algorithm: any SCC
+/// with more than one member, or a single node with a self-loop, is a cycle. TypeScript project
+/// references must form a DAG, and `tsc -b` rejects cycles with an opaque error, so it's worth
+/// catching here and reporting the packages involved. The error lists the members of the cycle
+/// sorted by name, not in the order their dependency edges run.
+fn detect_dependency_cycle(
+    package_manifest_by_package_name: &HashMap<String, PackageManifest>,
+) -> Result<(), LinkError> {
+    let adjacency: HashMap<String, Vec<String>> = package_manifest_by_package_name
+        .values()
+        .map(|package_manifest| {
+            let dependency_names: Vec<String> = package_manifest
+                .internal_dependencies_iter(package_manifest_by_package_name)
+                .map(|dependency| dependency.name().to_owned())
+                .collect();
+            (package_manifest.name().to_owned(), dependency_names)
+        })
+        .collect();
+
+    match find_cycle(&adjacency) {
+        Some(cycle) => Err(LinkError {
+            kind: LinkErrorKind::DependencyCycle(cycle),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Returns the members (sorted) of the first strongly-connected component with more than one
+/// member, or with a self-loop, found while walking `adjacency` in package-name order. `None`
+/// means the graph is a DAG.
+fn find_cycle(adjacency: &HashMap<String, Vec<String>>) -> Option<Vec<String>> {
+    let mut state: HashMap<String, TarjanState> = HashMap::new();
+    let mut stack: Vec<String> = Vec::new();
+    let mut next_index = 0;
+
+    // Iterative Tarjan's, keyed by package name so the order we visit packages in is
+    // deterministic regardless of HashMap iteration order.
+    let mut package_names: Vec<&String> = adjacency.keys().collect();
+    package_names.sort_unstable();
+
+    for start in package_names {
+        if state.contains_key(start) {
+            continue;
+        }
+
+        // (node, next dependency index to visit) frames.
+        let mut call_stack: Vec<(String, usize)> = vec![(start.clone(), 0)];
+
+        while let Some((node, dependency_index)) = call_stack.pop() {
+            if dependency_index == 0 {
+                state.insert(
+                    node.clone(),
+                    TarjanState {
+                        index: next_index,
+                        lowlink: next_index,
+                        on_stack: true,
+                    },
+                );
+                next_index += 1;
+                stack.push(node.clone());
+            }
+
+            let dependencies = &adjacency[&node];
+            if let Some(dependency) = dependencies.get(dependency_index) {
+                call_stack.push((node.clone(), dependency_index + 1));
+
+                if !state.contains_key(dependency) {
+                    call_stack.push((dependency.clone(), 0));
+                } else if state[dependency].on_stack {
+                    let dependency_lowlink = state[dependency].index;
+                    let node_state = state.get_mut(&node).expect("Node should have been visited");
+                    node_state.lowlink = node_state.lowlink.min(dependency_lowlink);
+                }
+                continue;
+            }
+
+            // All of `node`'s dependencies have been visited; propagate its lowlink to its
+            // caller (the frame immediately below it, if any) and pop its SCC if it's a root.
+            let node_state_lowlink = state[&node].lowlink;
+            if let Some((caller, _)) = call_stack.last() {
+                let caller_state = state
+                    .get_mut(caller)
+                    .expect("Caller should have been visited");
+                caller_state.lowlink = caller_state.lowlink.min(node_state_lowlink);
+            }
+
+            if state[&node].lowlink == state[&node].index {
+                let mut scc = Vec::new();
+                loop {
+                    let member = stack.pop().expect("SCC root should still be on the stack");
+                    state
+                        .get_mut(&member)
+                        .expect("Stack member should have been visited")
+                        .on_stack = false;
+                    let is_root = member == node;
+                    scc.push(member);
+                    if is_root {
+                        break;
+                    }
+                }
+
+                let is_cycle = scc.len() > 1 || adjacency[&node].contains(&node);
+                if is_cycle {
+                    scc.sort_unstable();
+                    return Some(scc);
+                }
+            }
+        }
+    }
+
+    None
+}
+
 fn link_package_dependencies(
     root: &Path,
     action: Action,
-    lerna_manifest: &MonorepoManifest,
+    package_manifest_by_package_name: &HashMap<String, PackageManifest>,
+    patch_config: &PatchConfig,
 ) -> Result<bool, LinkError> {
-    // NOTE: this line calls LernaManifest::get_internal_package_manifests (the sloweset function) twice
-    let package_manifest_by_package_name = lerna_manifest.package_manifests_by_package_name()?;
-
     let tsconfig_diffs: Vec<Option<TypescriptConfig>> = package_manifest_by_package_name
         .values()
         .map(|package_manifest| {
             let package_directory = package_manifest.directory();
             let mut tsconfig = TypescriptConfig::from_directory(root, &package_directory)?;
             let internal_dependencies =
-                package_manifest.internal_dependencies_iter(&package_manifest_by_package_name);
+                package_manifest.internal_dependencies_iter(package_manifest_by_package_name);
 
             let desired_project_references: Vec<TypescriptProjectReference> = {
                 let mut typescript_project_references: Vec<String> = internal_dependencies
                     .into_iter()
                     .map(|dependency| {
-                        diff_paths(dependency.directory(), package_manifest.directory())
+                        let dependency_directory = patch_config.resolve_directory(
+                            package_manifest.name(),
+                            dependency.name(),
+                            &dependency.directory(),
+                        );
+                        diff_paths(dependency_directory, package_manifest.directory())
                             .expect(
                                 "Unable to calculate a relative path to dependency from package",
                             )
@@ -242,6 +378,115 @@ fn link_package_dependencies(
     Ok(is_exit_success)
 }
 
+// The `compilerOptions` shared by every internal package's project-reference build. Pulled out
+// into a single root `tsconfig.settings.json` so individual packages don't have to repeat them.
+fn desired_settings_contents() -> Map<String, Value> {
+    let compiler_options = serde_json::json!({
+        "composite": true,
+        "declaration": true,
+        "incremental": true,
+        "outDir": "lib",
+    });
+
+    let mut contents = Map::new();
+    contents.insert(String::from("compilerOptions"), compiler_options);
+    contents
+}
+
+// Create (or update) the root `tsconfig.settings.json`, and point each internal package's
+// `tsconfig.json` at it via `extends`, so the shared `compilerOptions` only need to be
+// maintained in one place.
+
+/// A package that already extends something other than our settings file is doing so on purpose
+/// (e.g. a project-specific base config, or a TypeScript 5.5+ array of multiple `extends`
+/// entries); don't clobber it. Only a matching string, or a missing/null `extends`, is ours to
+/// update. The comparison is against the package's actual computed `desired_extends` value, not
+/// just a filename suffix, so a different package's own `tsconfig.settings.json` (reached via a
+/// different relative path) isn't mistaken for ours.
+fn already_extends_something_else(current_extends: Option<&Value>, desired_extends: &str) -> bool {
+    match current_extends {
+        Some(Value::String(current)) => current != desired_extends,
+        Some(Value::Null) | None => false,
+        Some(_) => true,
+    }
+}
+
+fn link_settings_files(
+    root: &Path,
+    action: Action,
+    package_manifest_by_package_name: &HashMap<String, PackageManifest>,
+) -> Result<bool, LinkError> {
+    let mut is_exit_success = true;
+
+    let settings = TypescriptSettingsConfig::from_directory(root, Path::new(""))?;
+    let desired_settings_contents = desired_settings_contents();
+    if settings.contents != desired_settings_contents {
+        if action == Action::Lint {
+            is_exit_success = false;
+            println!(
+                "File has out-of-date settings: {:?}, expecting:",
+                settings.path()
+            );
+            let serialized = serde_json::to_string_pretty(&desired_settings_contents)
+                .expect("Should be able to serialize TypeScript settings");
+            println!("{}", serialized);
+        } else {
+            TypescriptSettingsConfig::write(
+                root,
+                TypescriptSettingsConfig {
+                    contents: desired_settings_contents,
+                },
+            )?;
+        }
+    }
+
+    package_manifest_by_package_name
+        .values()
+        .try_for_each(|package_manifest| -> Result<(), LinkError> {
+            let package_directory = package_manifest.directory();
+            let mut tsconfig = TypescriptConfig::from_directory(root, &package_directory)?;
+
+            let desired_extends = {
+                let relative = diff_paths(Path::new("tsconfig.settings.json"), &package_directory)
+                    .expect("Unable to calculate a relative path to the settings file from package")
+                    .to_str()
+                    .expect("Path not valid UTF-8 encoded")
+                    .to_string();
+                if relative.starts_with('.') {
+                    relative
+                } else {
+                    format!("./{relative}")
+                }
+            };
+
+            let current_extends = tsconfig.contents.get("extends");
+            if already_extends_something_else(current_extends, &desired_extends) {
+                return Ok(());
+            }
+            let needs_update = current_extends.and_then(Value::as_str) != Some(desired_extends.as_str());
+            if !needs_update {
+                return Ok(());
+            }
+
+            if action == Action::Lint {
+                is_exit_success = false;
+                println!(
+                    "File is missing an up-to-date `extends` of the shared settings file: {:?}, expecting {:?}",
+                    tsconfig.path(),
+                    desired_extends
+                );
+                Ok(())
+            } else {
+                tsconfig
+                    .contents
+                    .insert(String::from("extends"), Value::String(desired_extends));
+                Ok(TypescriptConfig::write(root, tsconfig)?)
+            }
+        })?;
+
+    Ok(is_exit_success)
+}
+
 pub fn link_typescript_project_references<P>(root: P, action: Action) -> Result<(), LinkError>
 where
     P: AsRef<Path>,
@@ -249,20 +494,116 @@ where
     let root = root.as_ref();
     let lerna_manifest =
         MonorepoManifest::from_directory(root).expect("Unable to read monorepo manifest");
-
-    let is_children_link_success = link_children_packages(root, action, &lerna_manifest)
-        .expect("Unable to link children packages");
-
-    let is_dependencies_link_success = link_package_dependencies(root, action, &lerna_manifest)
-        .expect("Unable to link internal package dependencies");
-
-    if action == Action::Lint && !(is_children_link_success && is_dependencies_link_success) {
+    let patch_config =
+        PatchConfig::from_directory(root).expect("Unable to read typescript-tools.toml");
+    let package_manifest_by_package_name = lerna_manifest
+        .package_manifests_by_package_name()
+        .expect("Unable to enumerate internal package manifests");
+
+    detect_dependency_cycle(&package_manifest_by_package_name)?;
+
+    let is_children_link_success =
+        link_children_packages(root, action, &package_manifest_by_package_name)
+            .expect("Unable to link children packages");
+
+    let is_dependencies_link_success = link_package_dependencies(
+        root,
+        action,
+        &package_manifest_by_package_name,
+        &patch_config,
+    )
+    .expect("Unable to link internal package dependencies");
+
+    let is_settings_link_success =
+        link_settings_files(root, action, &package_manifest_by_package_name)
+            .expect("Unable to link TypeScript settings files");
+
+    if action == Action::Lint
+        && !(is_children_link_success && is_dependencies_link_success && is_settings_link_success)
+    {
         return Err(LinkError {
             kind: LinkErrorKind::ProjectReferencesOutOfDate,
         });
     }
 
-    // TODO(7): create `tsconfig.settings.json` files
-
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn adjacency(pairs: &[(&str, &[&str])]) -> HashMap<String, Vec<String>> {
+        pairs
+            .iter()
+            .map(|(name, dependencies)| {
+                (
+                    (*name).to_owned(),
+                    dependencies.iter().map(|name| (*name).to_string()).collect(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn find_cycle_returns_none_for_a_dag() {
+        let adjacency = adjacency(&[("app", &["lib"]), ("lib", &["core"]), ("core", &[])]);
+        assert_eq!(find_cycle(&adjacency), None);
+    }
+
+    #[test]
+    fn find_cycle_detects_a_two_package_cycle() {
+        let adjacency = adjacency(&[("app", &["a"]), ("a", &["b"]), ("b", &["a"])]);
+        assert_eq!(find_cycle(&adjacency), Some(vec!["a".to_owned(), "b".to_owned()]));
+    }
+
+    #[test]
+    fn find_cycle_detects_a_self_loop() {
+        let adjacency = adjacency(&[("a", &["a"])]);
+        assert_eq!(find_cycle(&adjacency), Some(vec!["a".to_owned()]));
+    }
+
+    #[test]
+    fn already_extends_something_else_allows_missing_or_matching_extends() {
+        assert!(!already_extends_something_else(
+            None,
+            "./tsconfig.settings.json"
+        ));
+        assert!(!already_extends_something_else(
+            Some(&Value::Null),
+            "./tsconfig.settings.json"
+        ));
+        assert!(!already_extends_something_else(
+            Some(&Value::String("./tsconfig.settings.json".to_owned())),
+            "./tsconfig.settings.json"
+        ));
+    }
+
+    #[test]
+    fn already_extends_something_else_rejects_array_form_extends() {
+        let array = Value::Array(vec![Value::String("./some-other-base.json".to_owned())]);
+        assert!(already_extends_something_else(
+            Some(&array),
+            "./tsconfig.settings.json"
+        ));
+    }
+
+    #[test]
+    fn already_extends_something_else_rejects_a_different_string() {
+        let other = Value::String("./custom-base.json".to_owned());
+        assert!(already_extends_something_else(
+            Some(&other),
+            "./tsconfig.settings.json"
+        ));
+    }
+
+    #[test]
+    fn already_extends_something_else_rejects_a_different_packages_settings_file_with_the_same_filename(
+    ) {
+        let other = Value::String("../other-package/tsconfig.settings.json".to_owned());
+        assert!(already_extends_something_else(
+            Some(&other),
+            "./tsconfig.settings.json"
+        ));
+    }
+}
@@ -0,0 +1,23 @@
+use clap::ValueEnum;
+
+/// Whether a subsystem (`link`, the `tsconfig.settings.json` writer, ...) should report what it
+/// would change (`Lint`) or actually change it (`Write`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Action {
+    Lint,
+    Write,
+}
+
+/// The shape `query_internal_dependencies` should render the internal dependency graph in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum InternalDependenciesFormat {
+    /// `{ package name -> [dependency package names] }`
+    Name,
+    /// `{ package directory -> [dependency package directories] }`
+    Path,
+    /// A Graphviz/DOT digraph of package-name nodes and dependency edges.
+    Dot,
+    /// The dependency graph linearized into a valid build order (dependencies before
+    /// dependents), per Kahn's algorithm.
+    TopologicalOrder,
+}
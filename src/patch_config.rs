@@ -0,0 +1,212 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::{Error, ErrorKind};
+use std::path::{Path, PathBuf};
+
+use crate::io::FromFileError;
+
+const PATCH_CONFIG_FILENAME: &str = "typescript-tools.toml";
+
+#[derive(Debug, Default)]
+struct PatchTable {
+    /// The fallback override for an internal package, applied regardless of which package is
+    /// consuming it.
+    default: HashMap<String, PathBuf>,
+    /// Overrides scoped to a single consuming package, keyed by consumer name and then by
+    /// dependency name. These take precedence over `default`.
+    packages: HashMap<String, HashMap<String, PathBuf>>,
+}
+
+enum Section {
+    Default,
+    Package(String),
+}
+
+/// Parse the constrained subset of TOML this config file actually needs: `[patch.default]` and
+/// `[patch.packages."consumer-name"]` sections, each holding `"dependency-name" = "path"`
+/// entries. Hand-rolled rather than pulling in a TOML parsing library, in the same spirit as the
+/// JSONC reader in `typescript_config.rs`.
+fn parse_patch_toml(raw: &str) -> Result<PatchTable, Error> {
+    let mut table = PatchTable::default();
+    let mut section: Option<Section> = None;
+
+    for (line_number, raw_line) in raw.lines().enumerate() {
+        let line = strip_comment(raw_line).trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let malformed = |message: &str| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("{PATCH_CONFIG_FILENAME}:{}: {message}", line_number + 1),
+            )
+        };
+
+        if let Some(header) = line.strip_prefix('[').and_then(|rest| rest.strip_suffix(']')) {
+            section = Some(parse_section_header(header).ok_or_else(|| {
+                malformed(&format!("unrecognized section `[{header}]`"))
+            })?);
+            continue;
+        }
+
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| malformed("expected `\"name\" = \"path\"`"))?;
+        let key = unquote(key.trim());
+        let value = PathBuf::from(unquote(value.trim()));
+
+        match section.as_ref().ok_or_else(|| {
+            malformed("entry outside of a `[patch.default]` or `[patch.packages...]` section")
+        })? {
+            Section::Default => {
+                table.default.insert(key, value);
+            }
+            Section::Package(consumer_name) => {
+                table
+                    .packages
+                    .entry(consumer_name.clone())
+                    .or_default()
+                    .insert(key, value);
+            }
+        }
+    }
+
+    Ok(table)
+}
+
+fn parse_section_header(header: &str) -> Option<Section> {
+    if header == "patch.default" {
+        return Some(Section::Default);
+    }
+    header
+        .strip_prefix("patch.packages.")
+        .map(|consumer| Section::Package(unquote(consumer)))
+}
+
+fn unquote(value: &str) -> String {
+    value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .unwrap_or(value)
+        .to_owned()
+}
+
+/// Strip a trailing `#` comment, ignoring any `#` that appears inside a quoted string.
+fn strip_comment(line: &str) -> &str {
+    let mut in_string = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => in_string = !in_string,
+            '#' if !in_string => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
+
+/// Repo-level overrides (`typescript-tools.toml`, borrowing the idea of Cargo's `[patch]`) for
+/// the resolved directory of specific internal packages. Lets a team temporarily redirect a
+/// dependency to a vendored or relocated copy without editing every consuming package's
+/// manifest.
+#[derive(Debug, Default)]
+pub struct PatchConfig {
+    table: PatchTable,
+}
+
+impl PatchConfig {
+    /// Read `typescript-tools.toml` from the monorepo root. A missing file is not an error - it
+    /// just means there are no patches in effect.
+    pub fn from_directory(root: &Path) -> Result<Self, FromFileError> {
+        let path = root.join(PATCH_CONFIG_FILENAME);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let raw = fs::read_to_string(&path)?;
+        let table = parse_patch_toml(&raw)?;
+        Ok(Self { table })
+    }
+
+    /// Resolve the directory to use for `dependency_name` as seen from `consumer_name`. A patch
+    /// scoped to `consumer_name` wins over a `default` patch for the same dependency, which in
+    /// turn wins over `directory` (the location the dependency actually resolves to on disk).
+    pub fn resolve_directory(
+        &self,
+        consumer_name: &str,
+        dependency_name: &str,
+        directory: &Path,
+    ) -> PathBuf {
+        self.table
+            .packages
+            .get(consumer_name)
+            .and_then(|overrides| overrides.get(dependency_name))
+            .or_else(|| self.table.default.get(dependency_name))
+            .cloned()
+            .unwrap_or_else(|| directory.to_path_buf())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_patch_applies_to_any_consumer() {
+        let table = parse_patch_toml(
+            r#"
+            [patch.default]
+            "@scope/a" = "../vendor/a"
+            "#,
+        )
+        .unwrap();
+        let config = PatchConfig { table };
+
+        assert_eq!(
+            config.resolve_directory("@scope/consumer", "@scope/a", Path::new("packages/a")),
+            PathBuf::from("../vendor/a")
+        );
+    }
+
+    #[test]
+    fn package_scoped_patch_wins_over_default() {
+        let table = parse_patch_toml(
+            r#"
+            [patch.default]
+            "@scope/a" = "../vendor/a"
+
+            [patch.packages."@scope/consumer"]
+            "@scope/a" = "../vendor/a-for-consumer"
+            "#,
+        )
+        .unwrap();
+        let config = PatchConfig { table };
+
+        assert_eq!(
+            config.resolve_directory("@scope/consumer", "@scope/a", Path::new("packages/a")),
+            PathBuf::from("../vendor/a-for-consumer")
+        );
+        assert_eq!(
+            config.resolve_directory("@scope/other-consumer", "@scope/a", Path::new("packages/a")),
+            PathBuf::from("../vendor/a")
+        );
+    }
+
+    #[test]
+    fn unpatched_dependency_resolves_to_its_own_directory() {
+        let config = PatchConfig {
+            table: PatchTable::default(),
+        };
+
+        assert_eq!(
+            config.resolve_directory("@scope/consumer", "@scope/b", Path::new("packages/b")),
+            PathBuf::from("packages/b")
+        );
+    }
+
+    #[test]
+    fn rejects_entries_outside_a_section() {
+        let err = parse_patch_toml("\"@scope/a\" = \"../vendor/a\"").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,283 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Display;
+use std::path::Path;
+
+use crate::monorepo_manifest::{EnumeratePackageManifestsError, MonorepoManifest};
+use crate::opts::InternalDependenciesFormat;
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct QueryError {
+    pub kind: QueryErrorKind,
+}
+
+impl Display for QueryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "error querying internal dependencies")
+    }
+}
+
+impl std::error::Error for QueryError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.kind {
+            QueryErrorKind::EnumeratePackageManifests(err) => Some(err),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum QueryErrorKind {
+    #[non_exhaustive]
+    EnumeratePackageManifests(EnumeratePackageManifestsError),
+}
+
+impl From<EnumeratePackageManifestsError> for QueryError {
+    fn from(err: EnumeratePackageManifestsError) -> Self {
+        Self {
+            kind: QueryErrorKind::EnumeratePackageManifests(err),
+        }
+    }
+}
+
+/// Emit the internal dependency graph in the shape requested by `format`.
+pub fn query_internal_dependencies<P>(
+    root: P,
+    format: InternalDependenciesFormat,
+) -> Result<serde_json::Value, QueryError>
+where
+    P: AsRef<Path>,
+{
+    let root = root.as_ref();
+    let lerna_manifest = MonorepoManifest::from_directory(root)?;
+    let package_manifest_by_package_name = lerna_manifest.package_manifests_by_package_name()?;
+
+    match format {
+        InternalDependenciesFormat::Name => {
+            let dependencies_by_name: HashMap<String, Vec<String>> =
+                package_manifest_by_package_name
+                    .values()
+                    .map(|package_manifest| {
+                        let mut dependency_names: Vec<String> = package_manifest
+                            .internal_dependencies_iter(&package_manifest_by_package_name)
+                            .map(|dependency| dependency.name().to_owned())
+                            .collect();
+                        dependency_names.sort_unstable();
+                        (package_manifest.name().to_owned(), dependency_names)
+                    })
+                    .collect();
+            Ok(serde_json::to_value(dependencies_by_name)
+                .expect("Should be able to serialize internal dependencies by name"))
+        }
+        InternalDependenciesFormat::Path => {
+            let dependencies_by_path: HashMap<String, Vec<String>> =
+                package_manifest_by_package_name
+                    .values()
+                    .map(|package_manifest| {
+                        let mut dependency_paths: Vec<String> = package_manifest
+                            .internal_dependencies_iter(&package_manifest_by_package_name)
+                            .map(|dependency| path_to_string(dependency.directory()))
+                            .collect();
+                        dependency_paths.sort_unstable();
+                        (
+                            path_to_string(package_manifest.directory()),
+                            dependency_paths,
+                        )
+                    })
+                    .collect();
+            Ok(serde_json::to_value(dependencies_by_path)
+                .expect("Should be able to serialize internal dependencies by path"))
+        }
+        InternalDependenciesFormat::Dot => {
+            let dot = render_dot(&package_manifest_by_package_name);
+            Ok(serde_json::Value::String(dot))
+        }
+        InternalDependenciesFormat::TopologicalOrder => {
+            let order = topological_order(&package_manifest_by_package_name);
+            Ok(serde_json::to_value(order)
+                .expect("Should be able to serialize topological build order"))
+        }
+    }
+}
+
+fn path_to_string(path: impl AsRef<Path>) -> String {
+    path.as_ref()
+        .to_str()
+        .expect("Path not valid UTF-8 encoded")
+        .to_owned()
+}
+
+/// Build the package-name adjacency list shared by the `Dot` and `TopologicalOrder` formats:
+/// package name -> sorted list of internal dependency package names.
+fn adjacency_by_name(
+    package_manifest_by_package_name: &HashMap<String, crate::package_manifest::PackageManifest>,
+) -> HashMap<String, Vec<String>> {
+    package_manifest_by_package_name
+        .values()
+        .map(|package_manifest| {
+            let mut dependency_names: Vec<String> = package_manifest
+                .internal_dependencies_iter(package_manifest_by_package_name)
+                .map(|dependency| dependency.name().to_owned())
+                .collect();
+            dependency_names.sort_unstable();
+            (package_manifest.name().to_owned(), dependency_names)
+        })
+        .collect()
+}
+
+/// Render the internal dependency graph as a Graphviz/DOT digraph, e.g. `digraph { "a"; "a" ->
+/// "b"; }`. Nodes and edges are each emitted in sorted order so the output is deterministic
+/// across runs, and every package gets a node statement even if it has no internal dependencies
+/// or dependents.
+fn render_dot(
+    package_manifest_by_package_name: &HashMap<String, crate::package_manifest::PackageManifest>,
+) -> String {
+    render_dot_from_adjacency(&adjacency_by_name(package_manifest_by_package_name))
+}
+
+fn render_dot_from_adjacency(adjacency: &HashMap<String, Vec<String>>) -> String {
+    let mut nodes: Vec<String> = adjacency
+        .keys()
+        .map(|package_name| format!("\"{package_name}\";"))
+        .collect();
+    nodes.sort_unstable();
+
+    let mut edges: Vec<String> = adjacency
+        .iter()
+        .flat_map(|(package_name, dependency_names)| {
+            dependency_names
+                .iter()
+                .map(move |dependency_name| format!("\"{package_name}\" -> \"{dependency_name}\";"))
+        })
+        .collect();
+    edges.sort_unstable();
+
+    let mut dot = String::from("digraph {\n");
+    for node in nodes {
+        dot.push_str("  ");
+        dot.push_str(&node);
+        dot.push('\n');
+    }
+    for edge in edges {
+        dot.push_str("  ");
+        dot.push_str(&edge);
+        dot.push('\n');
+    }
+    dot.push('}');
+    dot
+}
+
+/// Linearize the internal dependency graph into a build order (dependencies before dependents)
+/// using Kahn's algorithm. Ties are broken by package name so the output is deterministic.
+///
+/// Any package left over once the queue is empty is part of a dependency cycle; rather than
+/// failing, those packages are appended (sorted) after the linearized prefix, since this query
+/// is read-only and `link_typescript_project_references` is responsible for rejecting cycles.
+fn topological_order(
+    package_manifest_by_package_name: &HashMap<String, crate::package_manifest::PackageManifest>,
+) -> Vec<String> {
+    topological_order_from_adjacency(&adjacency_by_name(package_manifest_by_package_name))
+}
+
+fn topological_order_from_adjacency(adjacency: &HashMap<String, Vec<String>>) -> Vec<String> {
+    let mut in_degree: HashMap<&str, usize> =
+        adjacency.keys().map(|name| (name.as_str(), 0)).collect();
+    for dependency_names in adjacency.values() {
+        for dependency_name in dependency_names {
+            if let Some(count) = in_degree.get_mut(dependency_name.as_str()) {
+                *count += 1;
+            }
+        }
+    }
+
+    // A package's in-degree here counts its *dependents* (edges point dependent -> dependency),
+    // so popping zero-in-degree nodes first yields dependents-before-dependencies; reverse at
+    // the end to get the conventional build order of dependencies-before-dependents.
+    let mut ready: VecDeque<String> = {
+        let mut names: Vec<String> = in_degree
+            .iter()
+            .filter(|(_, count)| **count == 0)
+            .map(|(name, _)| (*name).to_owned())
+            .collect();
+        names.sort_unstable();
+        names.into()
+    };
+
+    let mut order = Vec::with_capacity(adjacency.len());
+    while let Some(name) = ready.pop_front() {
+        order.push(name.clone());
+
+        let mut newly_ready = Vec::new();
+        for dependency_name in &adjacency[&name] {
+            let count = in_degree
+                .get_mut(dependency_name.as_str())
+                .expect("Dependency should have an in-degree entry");
+            *count -= 1;
+            if *count == 0 {
+                newly_ready.push(dependency_name.clone());
+            }
+        }
+        newly_ready.sort_unstable();
+        for name in newly_ready {
+            ready.push_back(name);
+        }
+    }
+
+    let mut cyclic: Vec<String> = adjacency
+        .keys()
+        .filter(|name| !order.contains(name))
+        .cloned()
+        .collect();
+    cyclic.sort_unstable();
+
+    order.reverse();
+    order.extend(cyclic);
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn adjacency(pairs: &[(&str, &[&str])]) -> HashMap<String, Vec<String>> {
+        pairs
+            .iter()
+            .map(|(name, dependencies)| {
+                (
+                    (*name).to_owned(),
+                    dependencies.iter().map(|name| (*name).to_string()).collect(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn render_dot_includes_a_node_statement_for_packages_without_edges() {
+        let adjacency = adjacency(&[("a", &["b"]), ("b", &[]), ("isolated", &[])]);
+        let dot = render_dot_from_adjacency(&adjacency);
+        assert_eq!(
+            dot,
+            "digraph {\n  \"a\";\n  \"b\";\n  \"isolated\";\n  \"a\" -> \"b\";\n}"
+        );
+    }
+
+    #[test]
+    fn render_dot_sorts_nodes_and_edges() {
+        let adjacency = adjacency(&[("b", &["a"]), ("a", &[])]);
+        let dot = render_dot_from_adjacency(&adjacency);
+        assert_eq!(dot, "digraph {\n  \"a\";\n  \"b\";\n  \"b\" -> \"a\";\n}");
+    }
+
+    #[test]
+    fn topological_order_places_dependencies_before_dependents() {
+        let adjacency = adjacency(&[("app", &["lib"]), ("lib", &["core"]), ("core", &[])]);
+        let order = topological_order_from_adjacency(&adjacency);
+        assert_eq!(order, vec!["core", "lib", "app"]);
+    }
+
+    #[test]
+    fn topological_order_appends_cyclic_packages_sorted_after_the_linearized_prefix() {
+        let adjacency = adjacency(&[("app", &["a"]), ("a", &["b"]), ("b", &["a"])]);
+        let order = topological_order_from_adjacency(&adjacency);
+        assert_eq!(order, vec!["app", "a", "b"]);
+    }
+}
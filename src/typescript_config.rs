@@ -0,0 +1,273 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::configuration_file::{ConfigurationFile, WriteError};
+use crate::io::FromFileError;
+
+const TSCONFIG_FILENAME: &str = "tsconfig.json";
+const TSCONFIG_SETTINGS_FILENAME: &str = "tsconfig.settings.json";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TypescriptProjectReference {
+    pub path: String,
+}
+
+/// The `tsconfig.json` belonging to a single internal package. `contents` is kept as a raw
+/// `serde_json::Value` map (rather than a fully-typed struct) since we only ever read/write a
+/// handful of well-known fields and want to preserve everything else untouched.
+#[derive(Debug)]
+pub struct TypescriptConfig {
+    directory: PathBuf,
+    pub contents: Map<String, Value>,
+}
+
+impl ConfigurationFile for TypescriptConfig {
+    fn path(&self) -> PathBuf {
+        self.directory.join(TSCONFIG_FILENAME)
+    }
+
+    fn from_directory(root: &Path, directory: &Path) -> Result<Self, FromFileError> {
+        let path = root.join(directory).join(TSCONFIG_FILENAME);
+        let contents = read_jsonc_map(&path)?;
+        Ok(Self {
+            directory: directory.to_path_buf(),
+            contents,
+        })
+    }
+
+    fn write(root: &Path, config: Self) -> Result<(), WriteError> {
+        crate::configuration_file::write_json(root, &config.path(), &Value::Object(config.contents))
+    }
+}
+
+/// The single `tsconfig.settings.json` living at the monorepo root, holding the `compilerOptions`
+/// shared by every internal package's project-reference build (`composite`, `declaration`,
+/// `incremental`, `outDir`, ...). Every package's `tsconfig.json` points back at this one file
+/// via `extends`, rather than each repeating the same options by hand.
+#[derive(Debug)]
+pub struct TypescriptSettingsConfig {
+    pub contents: Map<String, Value>,
+}
+
+impl ConfigurationFile for TypescriptSettingsConfig {
+    fn path(&self) -> PathBuf {
+        PathBuf::from(TSCONFIG_SETTINGS_FILENAME)
+    }
+
+    fn from_directory(root: &Path, directory: &Path) -> Result<Self, FromFileError> {
+        let path = root.join(directory).join(TSCONFIG_SETTINGS_FILENAME);
+        let contents = if path.exists() {
+            read_jsonc_map(&path)?
+        } else {
+            Map::new()
+        };
+        Ok(Self { contents })
+    }
+
+    fn write(root: &Path, config: Self) -> Result<(), WriteError> {
+        crate::configuration_file::write_json(root, &config.path(), &Value::Object(config.contents))
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TypescriptParentProjectReferenceContents {
+    #[serde(default)]
+    pub references: Vec<TypescriptProjectReference>,
+}
+
+/// The `tsconfig.json` sitting in a directory that has no source of its own, but exists purely
+/// to reference the internal packages nested beneath it.
+#[derive(Debug)]
+pub struct TypescriptParentProjectReference {
+    directory: PathBuf,
+    pub contents: TypescriptParentProjectReferenceContents,
+}
+
+impl ConfigurationFile for TypescriptParentProjectReference {
+    fn path(&self) -> PathBuf {
+        self.directory.join(TSCONFIG_FILENAME)
+    }
+
+    fn from_directory(root: &Path, directory: &Path) -> Result<Self, FromFileError> {
+        let path = root.join(directory).join(TSCONFIG_FILENAME);
+        let contents = if path.exists() {
+            let map = read_jsonc_map(&path)?;
+            serde_json::from_value(Value::Object(map))?
+        } else {
+            TypescriptParentProjectReferenceContents::default()
+        };
+        Ok(Self {
+            directory: directory.to_path_buf(),
+            contents,
+        })
+    }
+
+    fn write(root: &Path, config: Self) -> Result<(), WriteError> {
+        let value = serde_json::to_value(&config.contents)
+            .expect("Should be able to express TypeScript project references as JSON");
+        crate::configuration_file::write_json(root, &config.path(), &value)
+    }
+}
+
+/// Read a `tsconfig.json`-shaped file, tolerating the JSONC dialect (`//` and `/* */` comments,
+/// plus trailing commas) that `tsc` itself accepts but `serde_json` rejects outright.
+///
+/// Real tsconfig.json files are hand-maintained and routinely contain comments, so failing to
+/// read them here would make `link_package_dependencies` hard-fail on any such file.
+fn read_jsonc_map(path: &Path) -> Result<Map<String, Value>, FromFileError> {
+    let raw = fs::read_to_string(path)?;
+    let stripped = strip_jsonc(&raw);
+    Ok(serde_json::from_str(&stripped)?)
+}
+
+/// Strip `//` and `/* */` comments and trailing commas from a JSONC document, without disturbing
+/// byte offsets inside string literals (so a `//` or trailing comma appearing inside a quoted
+/// string is left alone). Comments and the whitespace they occupy are replaced with spaces
+/// (rather than removed outright) so that any error positions reported by `serde_json` still
+/// line up with the original file.
+fn strip_jsonc(input: &str) -> String {
+    #[derive(Clone, Copy, PartialEq)]
+    enum State {
+        Normal,
+        InString,
+        InStringEscape,
+        InLineComment,
+        InBlockComment,
+    }
+
+    let chars: Vec<char> = input.chars().collect();
+    let mut out = String::with_capacity(chars.len());
+    let mut state = State::Normal;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        match state {
+            State::Normal => {
+                if c == '"' {
+                    state = State::InString;
+                    out.push(c);
+                } else if c == '/' && chars.get(i + 1) == Some(&'/') {
+                    state = State::InLineComment;
+                    out.push(' ');
+                    out.push(' ');
+                    i += 1;
+                } else if c == '/' && chars.get(i + 1) == Some(&'*') {
+                    state = State::InBlockComment;
+                    out.push(' ');
+                    out.push(' ');
+                    i += 1;
+                } else if c == ',' {
+                    // Trailing comma: look ahead past whitespace/comments for a closing
+                    // `}` or `]`. Since comments haven't been stripped from the remainder yet,
+                    // walk forward by hand rather than re-using `out`.
+                    let mut j = i + 1;
+                    loop {
+                        while j < chars.len() && chars[j].is_whitespace() {
+                            j += 1;
+                        }
+                        if j + 1 < chars.len() && chars[j] == '/' && chars[j + 1] == '/' {
+                            while j < chars.len() && chars[j] != '\n' {
+                                j += 1;
+                            }
+                            continue;
+                        }
+                        if j + 1 < chars.len() && chars[j] == '/' && chars[j + 1] == '*' {
+                            j += 2;
+                            while j + 1 < chars.len() && !(chars[j] == '*' && chars[j + 1] == '/')
+                            {
+                                j += 1;
+                            }
+                            j += 2;
+                            continue;
+                        }
+                        break;
+                    }
+                    if matches!(chars.get(j), Some('}') | Some(']')) {
+                        out.push(' ');
+                    } else {
+                        out.push(c);
+                    }
+                } else {
+                    out.push(c);
+                }
+            }
+            State::InString => {
+                out.push(c);
+                if c == '\\' {
+                    state = State::InStringEscape;
+                } else if c == '"' {
+                    state = State::Normal;
+                }
+            }
+            State::InStringEscape => {
+                out.push(c);
+                state = State::InString;
+            }
+            State::InLineComment => {
+                if c == '\n' {
+                    state = State::Normal;
+                    out.push(c);
+                } else {
+                    out.push(' ');
+                }
+            }
+            State::InBlockComment => {
+                if c == '*' && chars.get(i + 1) == Some(&'/') {
+                    state = State::Normal;
+                    out.push(' ');
+                    out.push(' ');
+                    i += 1;
+                } else if c == '\n' {
+                    out.push('\n');
+                } else {
+                    out.push(' ');
+                }
+            }
+        }
+        i += 1;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::strip_jsonc;
+
+    #[test]
+    fn strips_line_comments() {
+        let input = "{\n  \"a\": 1, // trailing note\n  \"b\": 2\n}";
+        let stripped = strip_jsonc(input);
+        let value: serde_json::Value = serde_json::from_str(&stripped).unwrap();
+        assert_eq!(value["a"], 1);
+        assert_eq!(value["b"], 2);
+    }
+
+    #[test]
+    fn strips_block_comments() {
+        let input = "{ /* leading */ \"a\": 1 /* trailing */ }";
+        let stripped = strip_jsonc(input);
+        let value: serde_json::Value = serde_json::from_str(&stripped).unwrap();
+        assert_eq!(value["a"], 1);
+    }
+
+    #[test]
+    fn strips_trailing_commas() {
+        let input = "{ \"a\": [1, 2, 3,], \"b\": 2, }";
+        let stripped = strip_jsonc(input);
+        let value: serde_json::Value = serde_json::from_str(&stripped).unwrap();
+        assert_eq!(value["a"], serde_json::json!([1, 2, 3]));
+        assert_eq!(value["b"], 2);
+    }
+
+    #[test]
+    fn preserves_comment_like_content_inside_strings() {
+        let input = r#"{ "a": "https://example.com", "b": "not, a, trailing, comma," }"#;
+        let stripped = strip_jsonc(input);
+        let value: serde_json::Value = serde_json::from_str(&stripped).unwrap();
+        assert_eq!(value["a"], "https://example.com");
+        assert_eq!(value["b"], "not, a, trailing, comma,");
+    }
+}